@@ -14,10 +14,15 @@ use crate::js_errors::V8Exception;
 use crate::ops::*;
 use crate::shared_queue::SharedQueue;
 use crate::shared_queue::RECOMMENDED_SIZE;
+use futures::future::abortable;
+use futures::future::AbortHandle;
+use futures::future::Aborted;
 use futures::future::FutureExt;
 use futures::future::TryFutureExt;
+use futures::stream::once;
 use futures::stream::select;
 use futures::stream::FuturesUnordered;
+use futures::stream::SelectAll;
 use futures::stream::StreamExt;
 use futures::task::AtomicWaker;
 use futures::Future;
@@ -31,9 +36,18 @@ use std::ops::{Deref, DerefMut};
 use std::option::Option;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Once};
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+
+/// The amount of headroom (in bytes) granted to the V8 heap once the
+/// near-heap-limit callback fires. This doesn't prevent the isolate from
+/// running out of memory; it only buys V8 enough room to unwind the stack
+/// and deliver the termination exception instead of hard-aborting the
+/// process.
+const HEAP_LIMIT_SLACK_BYTES: usize = 10 * 1024 * 1024;
 
 /// A ZeroCopyBuf encapsulates a slice that's been borrowed from a JavaScript
 /// ArrayBuffer object. JavaScript objects can normally be garbage collected,
@@ -88,6 +102,26 @@ impl AsMut<[u8]> for ZeroCopyBuf {
   }
 }
 
+/// An owned Rust allocation that gets hooked up directly as the backing
+/// store of a V8 `ArrayBuffer`, instead of being memcpy'd into a freshly
+/// allocated one. `Op::SyncBuf`/`Op::AsyncBuf` carry this type for op
+/// responses that are large enough that the copy would be costly (see the
+/// `overflow_res_async*` tests, which used to pay for a 100 MB memcpy here).
+pub struct OwnedBuf(Box<[u8]>);
+
+impl From<Box<[u8]>> for OwnedBuf {
+  fn from(buf: Box<[u8]>) -> Self {
+    Self(buf)
+  }
+}
+
+impl Deref for OwnedBuf {
+  type Target = [u8];
+  fn deref(&self) -> &[u8] {
+    &self.0
+  }
+}
+
 pub enum SnapshotConfig {
   Borrowed(v8::StartupData<'static>),
   Owned(v8::OwnedStartupData),
@@ -149,6 +183,37 @@ pub enum StartupData<'a> {
 
 type JSErrorCreateFn = dyn Fn(V8Exception) -> ErrBox;
 type IsolateErrorHandleFn = dyn FnMut(ErrBox) -> Result<(), ErrBox>;
+// Identifies one `Deno.core.dispatch()` call, as opposed to `OpId` which
+// identifies the *op* (kind) it dispatched. Retrievable via
+// `Isolate::last_dispatch_id` right after dispatch, so
+// `Isolate::cancel_op` can target that one call even when several calls to
+// the same op are in flight at once.
+type DispatchId = u32;
+type PendingOpBufFuture =
+  Pin<Box<dyn Future<Output = Result<(OpId, OwnedBuf), ErrBox>>>>;
+// Tagged with the dispatching op's id so chunks from many concurrently
+// streaming ops can be merged into one `SelectAll` and still be routed
+// back to the right `setAsyncHandler` callback. `None`
+// marks end-of-stream; unlike a zero-length `Buf`, which a stream may
+// legitimately yield mid-stream, it can't be confused with a real chunk.
+type PendingOpStream =
+  Pin<Box<dyn futures::Stream<Item = Result<(OpId, Option<Buf>), ErrBox>>>>;
+
+/// Observes op dispatch and event-loop activity so embedders can build
+/// counters/histograms for op latency and throughput without patching
+/// `Isolate` itself. Install one via `Isolate::set_metrics_observer`.
+pub trait OpMetricsObserver {
+  /// Called from `dispatch_op`, once per `Deno.core.dispatch()` call.
+  /// `bytes_in` is the combined length of the control buffer and any
+  /// zero-copy buffers passed along with it.
+  fn op_dispatched(&self, op_id: OpId, is_sync: bool, bytes_in: usize);
+  /// Called once an op's result is about to be delivered back to JS,
+  /// whether synchronously or via `setAsyncHandler`.
+  fn op_completed(&self, op_id: OpId, is_sync: bool, bytes_out: usize);
+  /// Called when a response couldn't fit in the shared queue and had to
+  /// take the slower overflow route instead.
+  fn shared_queue_overflow(&self, op_id: OpId);
+}
 
 /// A single execution context of JavaScript. Corresponds roughly to the "Web
 /// Worker" concept in the DOM. An Isolate is a Future that can be used with
@@ -174,11 +239,51 @@ pub struct IsolateInner {
   pub(crate) shared: SharedQueue,
   pending_ops: FuturesUnordered<PendingOpFuture>,
   pending_unref_ops: FuturesUnordered<PendingOpFuture>,
+  // Async responses delivered via the zero-copy `Op::AsyncBuf` path bypass
+  // the shared queue entirely (see `dispatch_op`/`poll`), so they're kept
+  // in a queue of their own rather than `pending_ops`.
+  pending_buf_ops: FuturesUnordered<PendingOpBufFuture>,
+  // Lets `Isolate::cancel_op` abort one specific in-flight async op call by
+  // the `DispatchId` assigned to it in `dispatch_op` (see
+  // `Isolate::last_dispatch_id`). Each dispatch gets its own entry here, so
+  // two concurrent calls to the same op (same `OpId`) each have their own
+  // handle and canceling one can't reach the other.
+  pending_op_cancel_handles: HashMap<DispatchId, AbortHandle>,
+  next_dispatch_id: DispatchId,
+  last_dispatch_id: DispatchId,
+  // Chunks from `Op::AsyncStream` ops, merged across every currently
+  // streaming op. Each stream is chained with a final `None` item (see
+  // `dispatch_op`) that `poll` delivers via `stream_end_response` instead
+  // of `async_op_response`, so a legitimately empty chunk mid-stream can't
+  // be mistaken for end-of-stream.
+  pending_streams: SelectAll<PendingOpStream>,
   have_unpolled_ops: bool,
   startup_script: Option<OwnedScript>,
   pub op_registry: Rc<OpRegistry>,
   waker: AtomicWaker,
   error_handler: Option<Box<IsolateErrorHandleFn>>,
+  // Kept alive for as long as the near-heap-limit callback is installed:
+  // the callback is only ever invoked with the raw pointer we hand V8 in
+  // `Isolate::set_heap_limits`, so the handle it points at needs a stable
+  // address for the isolate's whole lifetime.
+  heap_limit_handle: Option<Box<v8::IsolateHandle>>,
+  // Cooperative execution deadline watchdog. `execution_deadline` is the
+  // duration set via `Isolate::set_execution_deadline`; the watchdog is
+  // re-armed for it at the start of every `execute()`/`poll()` turn.
+  // `deadline_armed` guards against spawning more than one watchdog thread
+  // per turn. `deadline_generation` is bumped every time a turn finishes
+  // (`reset_deadline_watchdog`, called from every turn-exit path, success
+  // or error); a watchdog thread compares its own generation against the
+  // current one when it wakes, and does nothing if they differ, so a
+  // watchdog armed for a turn that already finished can't reach into a
+  // later, unrelated turn and terminate it. `deadline_exceeded` is how the
+  // watchdog thread reports back that it, rather than anything else,
+  // called `terminate_execution`.
+  execution_deadline: Option<Duration>,
+  deadline_armed: Arc<AtomicBool>,
+  deadline_generation: Arc<AtomicUsize>,
+  deadline_exceeded: Arc<AtomicBool>,
+  metrics_observer: Option<Rc<dyn OpMetricsObserver>>,
   magic_number: usize,
 }
 
@@ -318,11 +423,22 @@ impl Isolate {
       needs_init,
       pending_ops: FuturesUnordered::new(),
       pending_unref_ops: FuturesUnordered::new(),
+      pending_buf_ops: FuturesUnordered::new(),
+      pending_op_cancel_handles: HashMap::new(),
+      next_dispatch_id: 0,
+      last_dispatch_id: 0,
+      pending_streams: SelectAll::new(),
       have_unpolled_ops: false,
       startup_script,
       op_registry: Rc::new(OpRegistry::new()),
       waker: AtomicWaker::new(),
       error_handler: None,
+      heap_limit_handle: None,
+      execution_deadline: None,
+      deadline_armed: Arc::new(AtomicBool::new(false)),
+      deadline_generation: Arc::new(AtomicUsize::new(0)),
+      deadline_exceeded: Arc::new(AtomicBool::new(false)),
+      metrics_observer: None,
       magic_number: 0xCAFE_BABE,
     };
 
@@ -363,6 +479,93 @@ impl Isolate {
     isolate
   }
 
+  /// Caps the V8 heap for this isolate between `initial` and `max` bytes and
+  /// installs a near-heap-limit callback so that approaching `max` becomes a
+  /// recoverable `ErrBox` instead of a hard OOM abort.
+  ///
+  /// When V8 calls back to say it's near the limit, we call
+  /// `terminate_execution` on the isolate's thread-safe handle and grant a
+  /// small amount of extra heap so V8 can unwind and throw rather than
+  /// crash. The termination surfaces through the ordinary exception path:
+  /// `handle_exception`'s `is_terminating_exception` branch turns it into a
+  /// JS exception, which `execute`/`poll` then translate into an `ErrBox`
+  /// via `check_last_exception`.
+  ///
+  /// Must not be called more than once: the callback holds a raw pointer
+  /// into `heap_limit_handle`, and a second call would overwrite that
+  /// `Box` (dropping the first handle) while V8 still has the old
+  /// callback, and its now-dangling pointer, registered.
+  pub fn set_heap_limits(&mut self, initial: usize, max: usize) {
+    let mut inner = self.0.borrow_mut();
+    assert!(
+      inner.heap_limit_handle.is_none(),
+      "Isolate::set_heap_limits must not be called more than once"
+    );
+    let v8_isolate = inner.v8_isolate.as_mut().unwrap();
+    v8_isolate.set_heap_limits(initial, max);
+    let handle = Box::new(v8_isolate.thread_safe_handle());
+    let data = handle.as_ref() as *const v8::IsolateHandle as *mut c_void;
+    v8_isolate.add_near_heap_limit_callback(near_heap_limit_callback, data);
+    inner.heap_limit_handle = Some(handle);
+  }
+
+  /// Arms a watchdog that calls `terminate_execution` if this isolate is
+  /// still running inside V8 past `duration`. Unlike the per-call
+  /// termination a caller could trigger manually, this gives hosts running
+  /// untrusted scripts a deadline without spawning a separate supervisor
+  /// isolate: the watchdog re-arms itself at the start of every
+  /// subsequent `execute()`/`poll()` turn, so the deadline applies per
+  /// turn rather than once for the isolate's whole lifetime. Pass
+  /// `Duration::new(0, 0)` to disable it again: a zero duration doesn't
+  /// arm a watchdog that fires instantly, it clears `execution_deadline`
+  /// outright.
+  pub fn set_execution_deadline(&mut self, duration: Duration) {
+    if duration == Duration::new(0, 0) {
+      self.0.borrow_mut().execution_deadline = None;
+      return;
+    }
+    self.0.borrow_mut().execution_deadline = Some(duration);
+    self.arm_deadline_watchdog();
+  }
+
+  fn arm_deadline_watchdog(&mut self) {
+    let duration = match self.0.borrow().execution_deadline {
+      Some(duration) => duration,
+      None => return,
+    };
+    if self.0.borrow().deadline_armed.swap(true, Ordering::SeqCst) {
+      // A watchdog thread for the current turn is already in flight.
+      return;
+    }
+    let generation = self.0.borrow().deadline_generation.load(Ordering::SeqCst);
+    let handle = self.shared_isolate_handle();
+    let deadline_exceeded = self.0.borrow().deadline_exceeded.clone();
+    let deadline_generation = self.0.borrow().deadline_generation.clone();
+    std::thread::spawn(move || {
+      std::thread::sleep(duration);
+      // If the turn this watchdog was armed for has since finished, the
+      // generation has moved on: this watchdog is stale and must not
+      // terminate whatever turn happens to be running now.
+      if deadline_generation.load(Ordering::SeqCst) == generation
+        && !handle.is_execution_terminating()
+      {
+        deadline_exceeded.store(true, Ordering::SeqCst);
+        handle.terminate_execution();
+      }
+    });
+  }
+
+  /// Marks the current turn as finished for the deadline watchdog: allows
+  /// the next `execute()`/`poll()` turn to arm a fresh watchdog, and bumps
+  /// the generation counter so a watchdog still sleeping from this turn
+  /// can't terminate a later one. Called from every turn-exit path,
+  /// whether it ended in success or in an error.
+  fn reset_deadline_watchdog(&mut self) {
+    let inner = self.0.borrow();
+    inner.deadline_armed.store(false, Ordering::SeqCst);
+    inner.deadline_generation.fetch_add(1, Ordering::SeqCst);
+  }
+
   pub fn exception_to_err_result<'a, T>(
     &mut self,
     scope: &mut impl v8::ToLocal<'a>,
@@ -422,13 +625,20 @@ impl Isolate {
   }
 
   /// Defines the how Deno.core.dispatch() acts.
-  /// Called whenever Deno.core.dispatch() is called in JavaScript. zero_copy_buf
-  /// corresponds to the second argument of Deno.core.dispatch().
+  /// Called whenever Deno.core.dispatch() is called in JavaScript. zero_copy_bufs
+  /// corresponds to the variadic `ArrayBufferView` arguments following the
+  /// control buffer in `Deno.core.dispatch()`, letting callers pass several
+  /// backing buffers (e.g. for scatter/gather I/O) in a single dispatch.
   ///
   /// Requires runtime to explicitly ask for op ids before using any of the ops.
+  /// Returns the assigned `OpId`, so registration order doesn't matter and
+  /// embedders composing multiple op sets don't need to coordinate ids by
+  /// hand. `shared_init` registers a builtin "ops" op backed by
+  /// `op_names_dispatcher` so JS can look ids up by name at startup instead
+  /// of hardcoding them (see `shared_queue.js`'s `Deno.core.ops()`).
   pub fn register_op<F>(&self, name: &str, op: F) -> OpId
   where
-    F: Fn(&[u8], Option<ZeroCopyBuf>) -> CoreOp + 'static,
+    F: Fn(&[u8], Vec<ZeroCopyBuf>) -> CoreOp + 'static,
   {
     self.0.borrow().op_registry.register(name, op)
   }
@@ -444,6 +654,49 @@ impl Isolate {
     inner.js_error_create = Arc::new(f);
   }
 
+  /// Installs a pluggable observer that receives callbacks for op dispatch
+  /// (sync vs async), op completion (with bytes in/out), and shared-queue
+  /// overflow events, so embedders can build counters/histograms for op
+  /// latency and throughput without patching core.
+  pub fn set_metrics_observer(&mut self, observer: Rc<dyn OpMetricsObserver>) {
+    self.0.borrow_mut().metrics_observer = Some(observer);
+  }
+
+  /// Aborts the in-flight async op
+  /// call identified by `dispatch_id` (see `Isolate::last_dispatch_id`), if
+  /// it's still pending. The canceled call's future is dropped without its
+  /// result ever reaching `setAsyncHandler`; other concurrent calls to the
+  /// same op are unaffected. Returns `false` if there was nothing to cancel.
+  pub fn cancel_op(&mut self, dispatch_id: DispatchId) -> bool {
+    let mut inner = self.0.borrow_mut();
+    match inner.pending_op_cancel_handles.remove(&dispatch_id) {
+      Some(handle) => {
+        handle.abort();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Returns the `DispatchId` assigned to the most recent cancelable async
+  /// dispatch (`Op::Async`, `Op::AsyncUnref`, `Op::AsyncBuf`) handled by
+  /// `dispatch_op`. Read right after a `Deno.core.dispatch()` call returns,
+  /// it identifies that call for a later `Isolate::cancel_op`.
+  pub fn last_dispatch_id(&self) -> DispatchId {
+    self.0.borrow().last_dispatch_id
+  }
+
+  /// Allocates the `DispatchId` for the next `dispatch_op` call, used to key
+  /// `pending_op_cancel_handles` so concurrent calls to the same op don't
+  /// collide.
+  fn next_dispatch_id(&mut self) -> DispatchId {
+    let mut inner = self.0.borrow_mut();
+    let id = inner.next_dispatch_id;
+    inner.next_dispatch_id = inner.next_dispatch_id.wrapping_add(1);
+    inner.last_dispatch_id = id;
+    id
+  }
+
   /// Get a thread safe handle on the isolate.
   pub fn shared_isolate_handle(&mut self) -> v8::IsolateHandle {
     let mut inner = self.0.borrow_mut();
@@ -454,6 +707,8 @@ impl Isolate {
   pub(crate) fn shared_init(&mut self) {
     if self.0.borrow().needs_init {
       self.0.borrow_mut().needs_init = false;
+      let op_registry = self.0.borrow().op_registry.clone();
+      self.register_op("ops", op_names_dispatcher(op_registry));
       js_check(
         self.execute("shared_queue.js", include_str!("shared_queue.js")),
       );
@@ -466,19 +721,31 @@ impl Isolate {
     }
   }
 
+  /// Dispatches one `Deno.core.dispatch()` call. For `Op::Sync`/`Op::SyncBuf`
+  /// the returned buffer is the op's actual result, delivered synchronously
+  /// as `dispatch()`'s return value; async ops keep returning `None` here,
+  /// same as before, with their result instead arriving later via
+  /// `setAsyncHandler`. For the cancelable async variants (`Op::Async`,
+  /// `Op::AsyncUnref`, `Op::AsyncBuf`) this call is also assigned a
+  /// `DispatchId`, retrievable via `last_dispatch_id()` right after this
+  /// returns, for a later `Isolate::cancel_op`; the return value is
+  /// unaffected by it.
   pub fn dispatch_op<'s>(
     &mut self,
     scope: &mut impl v8::ToLocal<'s>,
     op_id: OpId,
     control_buf: &[u8],
-    zero_copy_buf: Option<ZeroCopyBuf>,
+    zero_copy_bufs: Vec<ZeroCopyBuf>,
   ) -> Option<(OpId, Box<[u8]>)> {
+    let bytes_in = control_buf.len()
+      + zero_copy_bufs.iter().map(|buf| buf.len()).sum::<usize>();
+
     let maybe_op =
       self
         .0
         .borrow_mut()
         .op_registry
-        .call(op_id, control_buf, zero_copy_buf);
+        .call(op_id, control_buf, zero_copy_bufs);
 
     let op = match maybe_op {
       Some(op) => op,
@@ -491,27 +758,103 @@ impl Isolate {
       }
     };
 
+    let is_sync = matches!(op, Op::Sync(_) | Op::SyncBuf(_));
+    if let Some(observer) = self.0.borrow().metrics_observer.clone() {
+      observer.op_dispatched(op_id, is_sync, bytes_in);
+    }
+
     debug_assert_eq!(self.0.borrow().shared.size(), 0);
     match op {
       Op::Sync(buf) => {
+        if let Some(observer) = self.0.borrow().metrics_observer.clone() {
+          observer.op_completed(op_id, true, buf.len());
+        }
         // For sync messages, we always return the response via Deno.core.send's
         // return value. Sync messages ignore the op_id.
         let op_id = 0;
         Some((op_id, buf))
       }
       Op::Async(fut) => {
+        let dispatch_id = self.next_dispatch_id();
         let fut2 = fut.map_ok(move |buf| (op_id, buf));
-        self.0.borrow_mut().pending_ops.push(fut2.boxed_local());
+        let (abortable_fut, cancel_handle) = abortable(fut2);
+        self
+          .0
+          .borrow_mut()
+          .pending_op_cancel_handles
+          .insert(dispatch_id, cancel_handle);
+        let inner = self.0.clone();
+        let fut3 = abortable_fut.map(move |result| {
+          inner.borrow_mut().pending_op_cancel_handles.remove(&dispatch_id);
+          result.unwrap_or_else(|Aborted| Err(OpCanceled.into()))
+        });
+        self.0.borrow_mut().pending_ops.push(fut3.boxed_local());
         self.0.borrow_mut().have_unpolled_ops = true;
         None
       }
       Op::AsyncUnref(fut) => {
+        let dispatch_id = self.next_dispatch_id();
         let fut2 = fut.map_ok(move |buf| (op_id, buf));
+        let (abortable_fut, cancel_handle) = abortable(fut2);
+        self
+          .0
+          .borrow_mut()
+          .pending_op_cancel_handles
+          .insert(dispatch_id, cancel_handle);
+        let inner = self.0.clone();
+        let fut3 = abortable_fut.map(move |result| {
+          inner.borrow_mut().pending_op_cancel_handles.remove(&dispatch_id);
+          result.unwrap_or_else(|Aborted| Err(OpCanceled.into()))
+        });
         self
           .0
           .borrow_mut()
           .pending_unref_ops
-          .push(fut2.boxed_local());
+          .push(fut3.boxed_local());
+        self.0.borrow_mut().have_unpolled_ops = true;
+        None
+      }
+      // Op::SyncBuf is returned the same way as Op::Sync: as a boxed slice
+      // in dispatch()'s return value. Only Op::AsyncBuf (below) reaches JS
+      // as an ArrayBuffer backed by the `OwnedBuf` itself, bypassing the
+      // shared queue (see `zero_copy_op_response`).
+      Op::SyncBuf(buf) => {
+        if let Some(observer) = self.0.borrow().metrics_observer.clone() {
+          observer.op_completed(op_id, true, buf.len());
+        }
+        let op_id = 0;
+        Some((op_id, buf.0))
+      }
+      // Lets an op stream its response back in chunks instead of buffering
+      // the whole payload (e.g. a file read or network body) before
+      // handing it to JS. A `None` item, appended after the op's own
+      // stream runs dry, marks end-of-stream; a real chunk is always
+      // `Some`, even if it happens to be zero-length, so the two can't be
+      // confused (see `stream_end_response`).
+      Op::AsyncStream(stream) => {
+        let tagged = stream
+          .map(move |item| item.map(|buf| (op_id, Some(buf))))
+          .chain(once(futures::future::ready(Ok((op_id, None)))))
+          .boxed_local();
+        self.0.borrow_mut().pending_streams.push(tagged);
+        self.0.borrow_mut().have_unpolled_ops = true;
+        None
+      }
+      Op::AsyncBuf(fut) => {
+        let dispatch_id = self.next_dispatch_id();
+        let fut2 = fut.map_ok(move |buf| (op_id, buf));
+        let (abortable_fut, cancel_handle) = abortable(fut2);
+        self
+          .0
+          .borrow_mut()
+          .pending_op_cancel_handles
+          .insert(dispatch_id, cancel_handle);
+        let inner = self.0.clone();
+        let fut3 = abortable_fut.map(move |result| {
+          inner.borrow_mut().pending_op_cancel_handles.remove(&dispatch_id);
+          result.unwrap_or_else(|Aborted| Err(OpCanceled.into()))
+        });
+        self.0.borrow_mut().pending_buf_ops.push(fut3.boxed_local());
         self.0.borrow_mut().have_unpolled_ops = true;
         None
       }
@@ -529,6 +872,7 @@ impl Isolate {
     js_source: &str,
   ) -> Result<(), ErrBox> {
     self.shared_init();
+    self.arm_deadline_watchdog();
 
     let mut hs =
       v8::HandleScope::new2(self.0.borrow_mut().v8_isolate.as_mut().unwrap());
@@ -547,7 +891,10 @@ impl Isolate {
     let mut script =
       v8::Script::compile(scope, context, source, Some(&origin)).unwrap();
     match script.run(scope, context) {
-      Some(_) => Ok(()),
+      Some(_) => {
+        self.reset_deadline_watchdog();
+        Ok(())
+      }
       None => {
         assert!(tc.has_caught());
         let exception = tc.exception().unwrap();
@@ -557,10 +904,17 @@ impl Isolate {
   }
 
   pub(crate) fn check_last_exception(&mut self) -> Result<(), ErrBox> {
+    // This is a turn-exit point (every error exit of `execute()`/`poll()`
+    // goes through it), so the deadline watchdog armed for this turn is
+    // done regardless of which branch below is taken.
+    self.reset_deadline_watchdog();
     let mut inner = self.0.borrow_mut();
     match inner.last_exception.take() {
       None => Ok(()),
       Some(json_str) => {
+        if inner.deadline_exceeded.swap(false, Ordering::SeqCst) {
+          return Err(ExecutionDeadlineExceeded.into());
+        }
         let v8_exception = V8Exception::from_json(&json_str).unwrap();
         drop(inner);
         let js_error = (self.0.borrow().js_error_create)(v8_exception);
@@ -569,9 +923,9 @@ impl Isolate {
     }
   }
 
-  pub(crate) fn attach_handle_to_error(
+  pub(crate) fn attach_handle_to_error<'a>(
     &mut self,
-    scope: &mut impl v8::InIsolate,
+    scope: &mut impl v8::ToLocal<'a>,
     err: ErrBox,
     handle: v8::Local<v8::Value>,
   ) -> ErrBox {
@@ -629,6 +983,75 @@ impl Isolate {
     }
   }
 
+  /// Signals end-of-stream for an `Op::AsyncStream` op. Called with exactly
+  /// one argument (the op id), as opposed to `async_op_response`'s two, so
+  /// it can never be mistaken for a (possibly zero-length) data chunk.
+  fn stream_end_response<'s>(
+    &mut self,
+    scope: &mut impl v8::ToLocal<'s>,
+    op_id: OpId,
+  ) -> Result<(), ErrBox> {
+    let context = scope.get_current_context().unwrap();
+    let global: v8::Local<v8::Value> = context.global(scope).into();
+    let js_recv_cb = self
+      .0
+      .borrow()
+      .js_recv_cb
+      .get(scope)
+      .expect("Deno.core.recv has not been called.");
+
+    // TODO(piscisaureus): properly integrate TryCatch in the scope chain.
+    let mut try_catch = v8::TryCatch::new(scope);
+    let tc = try_catch.enter();
+
+    let op_id: v8::Local<v8::Value> =
+      v8::Integer::new(scope, op_id as i32).into();
+    js_recv_cb.call(scope, context, global, &[op_id]);
+
+    match tc.exception() {
+      None => Ok(()),
+      Some(exception) => self.exception_to_err_result(scope, exception),
+    }
+  }
+
+  /// Delivers an `Op::AsyncBuf` response to `Deno.core.recv`'s callback.
+  /// Unlike `async_op_response`, the `OwnedBuf` is handed to V8 as the
+  /// backing store of a new `ArrayBuffer` rather than copied into one,
+  /// eliminating the memcpy the `overflow_res_*` tests used to pay for.
+  fn zero_copy_op_response<'s>(
+    &mut self,
+    scope: &mut impl v8::ToLocal<'s>,
+    op_id: OpId,
+    buf: OwnedBuf,
+  ) -> Result<(), ErrBox> {
+    let context = scope.get_current_context().unwrap();
+    let global: v8::Local<v8::Value> = context.global(scope).into();
+    let js_recv_cb = self
+      .0
+      .borrow()
+      .js_recv_cb
+      .get(scope)
+      .expect("Deno.core.recv has not been called.");
+
+    let mut try_catch = v8::TryCatch::new(scope);
+    let tc = try_catch.enter();
+
+    let backing_store =
+      v8::ArrayBuffer::new_backing_store_from_boxed_slice(buf.0);
+    let backing_store = v8::SharedRef::from(backing_store);
+    let ab = v8::ArrayBuffer::with_backing_store(scope, &backing_store);
+    let ui8 = v8::Uint8Array::new(ab, 0, ab.byte_length()).unwrap();
+
+    let op_id: v8::Local<v8::Value> = v8::Integer::new(scope, op_id as i32).into();
+    let ui8: v8::Local<v8::Value> = ui8.into();
+    js_recv_cb.call(scope, context, global, &[op_id, ui8]);
+
+    match tc.exception() {
+      None => Ok(()),
+      Some(exception) => self.exception_to_err_result(scope, exception),
+    }
+  }
+
   /// Takes a snapshot. The isolate should have been created with will_snapshot
   /// set to true.
   ///
@@ -662,6 +1085,7 @@ impl Future for Isolate {
     //let isolate = self.get_mut();
     isolate.0.borrow().waker.register(cx.waker());
     isolate.shared_init();
+    isolate.arm_deadline_watchdog();
 
     let mut hs = v8::HandleScope::new2(isolate.v8_isolate());
     let scope = hs.enter();
@@ -684,6 +1108,11 @@ impl Future for Isolate {
       */
       #[allow(clippy::match_wild_err_arm)]
       match isolate.0.borrow_mut().pending_ops.poll_next_unpin(cx) {
+        Poll::Ready(Some(Err(ref err))) if err.downcast_ref::<OpCanceled>().is_some() => {
+          // This call was canceled via `Isolate::cancel_op`. Drop its
+          // result rather than delivering it to `setAsyncHandler`.
+          continue;
+        }
         Poll::Ready(Some(Err(_))) => panic!("unexpected op error"),
         Poll::Ready(None) => break,
         Poll::Pending => break,
@@ -693,9 +1122,16 @@ impl Future for Isolate {
             // If we couldn't push the response to the shared queue, because
             // there wasn't enough size, we will return the buffer via the
             // legacy route, using the argument of deno_respond.
+            if let Some(observer) = isolate.0.borrow().metrics_observer.clone()
+            {
+              observer.shared_queue_overflow(op_id);
+            }
             overflow_response = Some((op_id, buf));
             break;
           }
+          if let Some(observer) = isolate.0.borrow().metrics_observer.clone() {
+            observer.op_completed(op_id, false, buf.len());
+          }
         }
       }
     }
@@ -708,23 +1144,116 @@ impl Future for Isolate {
 
     if overflow_response.is_some() {
       let (op_id, buf) = overflow_response.take().unwrap();
+      if let Some(observer) = isolate.0.borrow().metrics_observer.clone() {
+        observer.op_completed(op_id, false, buf.len());
+      }
       isolate.async_op_response(scope, Some((op_id, buf)))?;
     }
 
+    // Op::AsyncStream chunks are delivered as soon as each one resolves,
+    // same as overflowed/zero-copy responses, just one chunk at a time
+    // instead of the whole payload at once.
+    loop {
+      isolate.0.borrow_mut().have_unpolled_ops = false;
+      match isolate.0.borrow_mut().pending_streams.poll_next_unpin(cx) {
+        // A chunk source (e.g. a file read or network body) can fail
+        // mid-stream; return the error from `poll` to the embedder instead
+        // of panicking the whole isolate over what's routinely a
+        // recoverable I/O error.
+        Poll::Ready(Some(Err(err))) => {
+          isolate.reset_deadline_watchdog();
+          return Poll::Ready(Err(err));
+        }
+        Poll::Ready(None) => break,
+        Poll::Pending => break,
+        Poll::Ready(Some(Ok((op_id, Some(buf))))) => {
+          if let Some(observer) = isolate.0.borrow().metrics_observer.clone() {
+            observer.op_completed(op_id, false, buf.len());
+          }
+          isolate.async_op_response(scope, Some((op_id, buf)))?;
+        }
+        Poll::Ready(Some(Ok((op_id, None)))) => {
+          isolate.stream_end_response(scope, op_id)?;
+        }
+      }
+    }
+
+    // Op::AsyncBuf responses skip the shared queue entirely; each one is
+    // delivered to JS as soon as it resolves, backed directly by the
+    // response's own allocation instead of a copy into it.
+    loop {
+      isolate.0.borrow_mut().have_unpolled_ops = false;
+      #[allow(clippy::match_wild_err_arm)]
+      match isolate.0.borrow_mut().pending_buf_ops.poll_next_unpin(cx) {
+        Poll::Ready(Some(Err(ref err))) if err.downcast_ref::<OpCanceled>().is_some() => {
+          // This call was canceled via `Isolate::cancel_op`. Drop its
+          // result rather than delivering it to `setAsyncHandler`.
+          continue;
+        }
+        Poll::Ready(Some(Err(_))) => panic!("unexpected op error"),
+        Poll::Ready(None) => break,
+        Poll::Pending => break,
+        Poll::Ready(Some(Ok((op_id, buf)))) => {
+          if let Some(observer) = isolate.0.borrow().metrics_observer.clone() {
+            observer.op_completed(op_id, false, buf.len());
+          }
+          isolate.zero_copy_op_response(scope, op_id, buf)?;
+        }
+      }
+    }
+
     isolate.check_promise_exceptions(scope)?;
 
     // We're idle if pending_ops is empty.
-    if isolate.0.borrow().pending_ops.is_empty() {
+    if isolate.0.borrow().pending_ops.is_empty()
+      && isolate.0.borrow().pending_buf_ops.is_empty()
+      && isolate.0.borrow().pending_streams.is_empty()
+    {
+      isolate.reset_deadline_watchdog();
       Poll::Ready(Ok(()))
     } else {
       if isolate.0.borrow().have_unpolled_ops {
         isolate.0.borrow().waker.wake();
       }
+      isolate.reset_deadline_watchdog();
       Poll::Pending
     }
   }
 }
 
+/// Builds the dispatcher for the builtin "ops" op that backs
+/// `Deno.core.ops()`: it takes a snapshot of the op registry's name -> id
+/// map and returns it to JS as a JSON object, so op ids become
+/// order-independent and discoverable by name instead of hardcoded
+/// integers.
+fn op_names_dispatcher(
+  op_registry: Rc<OpRegistry>,
+) -> impl Fn(&[u8], Vec<ZeroCopyBuf>) -> CoreOp {
+  move |_control: &[u8], _zero_copy_bufs: Vec<ZeroCopyBuf>| -> CoreOp {
+    let json = ops_to_json(&op_registry.names());
+    Op::Sync(json.into_bytes().into_boxed_slice())
+  }
+}
+
+fn ops_to_json(names: &HashMap<String, OpId>) -> String {
+  // `Debug`-formatting the name isn't JSON escaping: it diverges from the
+  // JSON spec for non-ASCII and control characters. Let serde_json do the
+  // actual serialization instead of hand-rolling it.
+  serde_json::to_string(names).unwrap()
+}
+
+extern "C" fn near_heap_limit_callback(
+  data: *mut c_void,
+  current_heap_limit: usize,
+  _initial_heap_limit: usize,
+) -> usize {
+  // SAFETY: `data` was set up in `Isolate::set_heap_limits` to point at a
+  // `v8::IsolateHandle` that is kept alive for the isolate's lifetime.
+  let handle = unsafe { &*(data as *const v8::IsolateHandle) };
+  handle.terminate_execution();
+  current_heap_limit + HEAP_LIMIT_SLACK_BYTES
+}
+
 pub fn js_check<T>(r: Result<T, ErrBox>) -> T {
   if let Err(e) = r {
     panic!(e.to_string());
@@ -779,7 +1308,7 @@ pub mod tests {
     let mut isolate = Isolate::new(StartupData::None, false);
 
     let dispatcher =
-      move |control: &[u8], _zero_copy: Option<ZeroCopyBuf>| -> CoreOp {
+      move |control: &[u8], _zero_copy_bufs: Vec<ZeroCopyBuf>| -> CoreOp {
         dispatch_count_.fetch_add(1, Ordering::Relaxed);
         match mode {
           Mode::Async => {
@@ -824,8 +1353,11 @@ pub mod tests {
             let mut vec = Vec::<u8>::new();
             vec.resize(100 * 1024 * 1024, 0);
             vec[0] = 4;
-            let buf = vec.into_boxed_slice();
-            Op::Async(futures::future::ok(buf).boxed())
+            // Exercises the zero-copy Op::AsyncBuf path: the 100 MB buffer
+            // is handed to V8 as an ArrayBuffer backing store instead of
+            // being memcpy'd into one.
+            let buf: OwnedBuf = vec.into_boxed_slice().into();
+            Op::AsyncBuf(futures::future::ok(buf).boxed())
           }
         }
       };
@@ -1085,6 +1617,38 @@ pub mod tests {
     });
   }
 
+  #[test]
+  fn overflow_res_async_canceled() {
+    // Mirrors `overflow_res_async`, except the dispatched op is canceled
+    // before its future resolves: its result should never reach the
+    // `setAsyncHandler` callback.
+    run_in_task(|cx| {
+      let (mut isolate, dispatch_count) = setup(Mode::OverflowResAsync);
+      js_check(isolate.execute(
+        "overflow_res_async_canceled.js",
+        r#"
+         let asyncRecv = 0;
+         Deno.core.setAsyncHandler(1, (buf) => { asyncRecv++ });
+         // Large response that bypasses the shared space (Op::AsyncBuf).
+         let control = new Uint8Array([42]);
+         let response = Deno.core.dispatch(1, control);
+         assert(response == null);
+         assert(asyncRecv == 0);
+         "#,
+      ));
+      assert_eq!(dispatch_count.load(Ordering::Relaxed), 1);
+      let dispatch_id = isolate.last_dispatch_id();
+      assert!(isolate.cancel_op(dispatch_id));
+      // Canceling twice has nothing left to cancel.
+      assert!(!isolate.cancel_op(dispatch_id));
+      assert!(match isolate.poll_unpin(cx) {
+        Poll::Ready(Ok(_)) => true,
+        _ => false,
+      });
+      js_check(isolate.execute("check.js", "assert(asyncRecv == 0);"));
+    });
+  }
+
   #[test]
   fn test_pre_dispatch() {
     run_in_task(|mut cx| {
@@ -1139,26 +1703,149 @@ pub mod tests {
   }
 }
 
+/// A distinguished error returned by `execute`/`poll` when a watchdog armed
+/// via `Isolate::set_execution_deadline` terminated the isolate, as opposed
+/// to a plain uncaught JS exception. Embedders can downcast an `ErrBox` to
+/// this type to tell the two apart.
+#[derive(Debug)]
+pub struct ExecutionDeadlineExceeded;
+
+impl fmt::Display for ExecutionDeadlineExceeded {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "execution deadline exceeded")
+  }
+}
+
+impl Error for ExecutionDeadlineExceeded {}
+
+/// Internal marker wrapped in the `Err` of a `pending_ops`/`pending_buf_ops`
+/// future to signal that `Isolate::cancel_op` aborted this particular call.
+/// `poll` downcasts for it to drop the result silently instead of
+/// delivering it to `setAsyncHandler`; it never escapes `Isolate` as a
+/// public error, unlike `ExecutionDeadlineExceeded`.
+#[derive(Debug)]
+struct OpCanceled;
+
+impl fmt::Display for OpCanceled {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "op canceled")
+  }
+}
+
+impl Error for OpCanceled {}
+
+/// A single frame of a JS stack trace, as captured at the moment the
+/// exception was thrown. Until rusty_v8 exposes `v8::StackTrace` directly,
+/// this is parsed out of the `"    at ..."` lines of `Error.stack` rather
+/// than pulled from a structured API.
+#[derive(Debug, Clone)]
+pub struct JsStackFrame {
+  pub raw: String,
+}
+
 // TODO(piscisaureus): rusty_v8 should implement the Error trait on
 // values of type v8::Global<T>.
+//
+// Besides the handle to the thrown value, this holds the structured
+// exception info extracted from it at construction time -- message, the
+// `.stack` string, and the throw site's source location -- since the
+// `v8::Local` handles behind them don't outlive the scope they were made
+// in, but embedders may want to inspect or render this after that scope
+// is gone.
 pub struct ErrWithV8Handle {
   err: ErrBox,
   handle: v8::Global<v8::Value>,
+  message: String,
+  stack: Option<String>,
+  script_resource_name: Option<String>,
+  line_number: Option<i64>,
+  start_column: Option<i64>,
 }
 
 impl ErrWithV8Handle {
-  pub fn new(
-    scope: &mut impl v8::InIsolate,
+  pub fn new<'a>(
+    scope: &mut impl v8::ToLocal<'a>,
     err: ErrBox,
     handle: v8::Local<v8::Value>,
   ) -> Self {
+    let v8_message = v8::Exception::create_message(scope, handle);
+    let message = v8_message.get(scope).to_rust_string_lossy(scope);
+    let script_resource_name = v8_message
+      .get_script_resource_name(scope)
+      .map(|name| name.to_rust_string_lossy(scope));
+    let line_number = v8_message.get_line_number(scope).map(|n| n as i64);
+    let start_column = Some(i64::from(v8_message.get_start_column()));
+
+    let stack = handle.to_object(scope).and_then(|obj| {
+      let key = v8::String::new(scope, "stack").unwrap();
+      obj.get(scope, key.into()).and_then(|value| {
+        if value.is_string() {
+          Some(value.to_rust_string_lossy(scope))
+        } else {
+          None
+        }
+      })
+    });
+
     let handle = v8::Global::new_from(scope, handle);
-    Self { err, handle }
+    Self {
+      err,
+      handle,
+      message,
+      stack,
+      script_resource_name,
+      line_number,
+      start_column,
+    }
   }
 
   pub fn get_handle(&mut self) -> &mut v8::Global<v8::Value> {
     &mut self.handle
   }
+
+  /// The formatted exception message, e.g. `"Uncaught TypeError: ..."`.
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  /// The raw `Error.stack` string, if the thrown value was an `Error`
+  /// (or otherwise had a `.stack` property).
+  pub fn stack(&self) -> Option<&str> {
+    self.stack.as_deref()
+  }
+
+  /// The name of the script the exception was thrown from, if known.
+  pub fn script_resource_name(&self) -> Option<&str> {
+    self.script_resource_name.as_deref()
+  }
+
+  /// The 1-based line number of the throw site, if known.
+  pub fn line_number(&self) -> Option<i64> {
+    self.line_number
+  }
+
+  /// The 0-based column of the throw site, if known.
+  pub fn start_column(&self) -> Option<i64> {
+    self.start_column
+  }
+
+  /// The individual frames of the captured stack trace, so callers can
+  /// format them however they like instead of being stuck with the
+  /// flattened `.stack` string.
+  pub fn frames(&self) -> Vec<JsStackFrame> {
+    match &self.stack {
+      None => Vec::new(),
+      Some(stack) => stack
+        .lines()
+        // The first line is the formatted message, not a frame.
+        .skip(1)
+        .filter(|line| line.trim_start().starts_with("at "))
+        .map(|line| JsStackFrame {
+          raw: line.trim().to_string(),
+        })
+        .collect(),
+    }
+  }
 }
 
 unsafe impl Send for ErrWithV8Handle {}
@@ -1,8 +1,11 @@
 use deno_core::plugin_api::Buf;
 use deno_core::plugin_api::Interface;
 use deno_core::plugin_api::Op;
+use deno_core::plugin_api::OpError;
+use deno_core::plugin_api::Resource;
 use deno_core::plugin_api::ZeroCopyBuf;
 use futures::future::FutureExt;
+use futures::select;
 
 #[no_mangle]
 pub fn deno_plugin_init(interface: &mut dyn Interface) {
@@ -14,11 +17,17 @@ pub fn deno_plugin_init(interface: &mut dyn Interface) {
 fn op_test_sync(
   _interface: &mut dyn Interface,
   data: &[u8],
-  zero_copy: Option<ZeroCopyBuf>,
+  zero_copy: Vec<ZeroCopyBuf>,
 ) -> Op {
-  if let Some(buf) = zero_copy {
-    let data_str = std::str::from_utf8(&data[..]).unwrap();
-    let buf_str = std::str::from_utf8(&buf[..]).unwrap();
+  let data_str = match std::str::from_utf8(&data[..]) {
+    Ok(s) => s,
+    Err(err) => return Op::Sync(Err(OpError::from(err))),
+  };
+  for buf in zero_copy.iter() {
+    let buf_str = match std::str::from_utf8(&buf[..]) {
+      Ok(s) => s,
+      Err(err) => return Op::Sync(Err(OpError::from(err))),
+    };
     println!(
       "Hello from plugin. data: {} | zero_copy: {}",
       data_str, buf_str
@@ -26,18 +35,27 @@ fn op_test_sync(
   }
   let result = b"test";
   let result_box: Buf = Box::new(*result);
-  Op::Sync(result_box)
+  Op::Sync(Ok(result_box))
 }
 
 fn op_test_async(
-  _interface: &mut dyn Interface,
+  interface: &mut dyn Interface,
   data: &[u8],
-  zero_copy: Option<ZeroCopyBuf>,
+  zero_copy: Vec<ZeroCopyBuf>,
 ) -> Op {
-  let data_str = std::str::from_utf8(&data[..]).unwrap().to_string();
+  let data_str = match std::str::from_utf8(&data[..]) {
+    Ok(s) => s.to_string(),
+    Err(err) => {
+      return Op::Async(futures::future::err(OpError::from(err)).boxed())
+    }
+  };
+  // Resolves once the caller drops interest in this op (e.g. the JS promise
+  // is no longer reachable), so the future below can bail out early instead
+  // of riding out the full `Duration::from_secs(1)` worker sleep.
+  let cancel = interface.cancel_handle();
   let fut = async move {
-    if let Some(buf) = zero_copy {
-      let buf_str = std::str::from_utf8(&buf[..]).unwrap();
+    for buf in zero_copy.iter() {
+      let buf_str = std::str::from_utf8(&buf[..]).map_err(OpError::from)?;
       println!(
         "Hello from plugin. data: {} | zero_copy: {}",
         data_str, buf_str
@@ -46,12 +64,35 @@ fn op_test_async(
     let (tx, rx) = futures::channel::oneshot::channel::<Result<(), ()>>();
     std::thread::spawn(move || {
       std::thread::sleep(std::time::Duration::from_secs(1));
-      tx.send(Ok(())).unwrap();
+      // The receiver may already be gone if the op was canceled; that's fine.
+      let _ = tx.send(Ok(()));
     });
-    assert!(rx.await.is_ok());
+    let mut rx = rx.fuse();
+    let mut cancel = cancel.cancelled().fuse();
+    select! {
+      res = rx => res
+        .map_err(|_| {
+          OpError::from(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "op_test_async: worker thread disconnected",
+          ))
+        })?
+        .map_err(|_| {
+          OpError::from(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "op_test_async: worker thread reported failure",
+          ))
+        })?,
+      _ = cancel => {
+        return Err(OpError::from(std::io::Error::new(
+          std::io::ErrorKind::Interrupted,
+          "op_test_async: canceled",
+        )));
+      }
+    };
     let result = b"test";
     let result_box: Buf = Box::new(*result);
-    result_box
+    Ok(result_box)
   };
 
   Op::Async(fut.boxed())
@@ -61,10 +102,18 @@ struct TestResource {
   noise: String,
 }
 
+impl Resource for TestResource {
+  fn close(&mut self) {
+    // Stands in for the cleanup a real resource would need: flushing a
+    // file handle, joining a background thread, freeing an FFI pointer.
+    println!("TestResource closed, noise was: {}", self.noise);
+  }
+}
+
 fn op_test_resources(
   interface: &mut dyn Interface,
   _data: &[u8],
-  _zero_copy: Option<ZeroCopyBuf>,
+  _zero_copy: Vec<ZeroCopyBuf>,
 ) -> Op {
   let rid = {
     // `add()`
@@ -119,7 +168,8 @@ fn op_test_resources(
     assert!(!found2);
   }
   {
-    // add and leave in table
+    // add and leave in table; `Resource::close()` still runs for this one
+    // when the table is torn down along with the rest of the interface.
     let rc = Box::new(TestResource {
       noise: "woof".to_owned(),
     });
@@ -127,5 +177,5 @@ fn op_test_resources(
     let mut rt = rt.borrow_mut();
     rt.add("test_resource", rc);
   }
-  Op::Sync(Default::default())
+  Op::Sync(Ok(Default::default()))
 }